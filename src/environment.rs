@@ -0,0 +1,151 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::value::LoxValue;
+use crate::{Error, Result, Token};
+
+#[derive(Debug)]
+struct Scope {
+  values: HashMap<String, LoxValue>,
+  enclosing: Option<Rc<RefCell<Scope>>>,
+}
+
+impl Scope {
+  fn new(enclosing: Option<Rc<RefCell<Scope>>>) -> Self {
+    Scope {
+      values: HashMap::new(),
+      enclosing,
+    }
+  }
+}
+
+// A chain of lexical scopes. Cloning an Environment just clones the handle to
+// the innermost scope, not the scopes themselves, so a closure can hang on
+// to whatever scope was active when it was created even after the block
+// that created it has finished executing.
+#[derive(Debug, Clone)]
+pub struct Environment {
+  scope: Rc<RefCell<Scope>>,
+}
+
+impl Environment {
+  pub fn new() -> Self {
+    Environment {
+      scope: Rc::new(RefCell::new(Scope::new(None))),
+    }
+  }
+
+  // A fresh scope enclosed by this one, without disturbing `self`. Used to
+  // set up the call environment for a function invocation.
+  pub fn child(&self) -> Self {
+    Environment {
+      scope: Rc::new(RefCell::new(Scope::new(Some(self.scope.clone())))),
+    }
+  }
+
+  pub fn push_scope(&mut self) {
+    *self = self.child();
+  }
+
+  pub fn pop_scope(&mut self) {
+    let parent = self
+      .scope
+      .borrow()
+      .enclosing
+      .clone()
+      .expect("pop_scope called on the global scope");
+
+    self.scope = parent;
+  }
+
+  pub fn define(&mut self, name: &str, value: LoxValue) {
+    self.scope.borrow_mut().values.insert(name.to_string(), value);
+  }
+
+  // Reads/writes the root scope only (the one whose `enclosing` is `None`),
+  // skipping every scope in between. This is what the resolver means by
+  // "global" for a name it couldn't pin to a lexical distance: the name
+  // either really is global, or it's a forward reference to a not-yet-
+  // declared local, and in neither case should we walk the dynamic chain
+  // and risk hitting a same-named binding in some intermediate scope.
+  fn globals(&self) -> Rc<RefCell<Scope>> {
+    let mut scope = self.scope.clone();
+
+    loop {
+      let parent = scope.borrow().enclosing.clone();
+      match parent {
+        Some(p) => scope = p,
+        None => return scope,
+      }
+    }
+  }
+
+  pub fn get_global(&self, token: &Token) -> Result<LoxValue> {
+    self
+      .globals()
+      .borrow()
+      .values
+      .get(&token.lexeme)
+      .cloned()
+      .ok_or_else(|| {
+        Error::Runtime(
+          token.clone(),
+          format!("undefined variable '{}'", token.lexeme),
+        )
+      })
+  }
+
+  pub fn assign_global(&mut self, token: &Token, value: LoxValue) -> Result<()> {
+    let globals = self.globals();
+    let mut globals = globals.borrow_mut();
+
+    if !globals.values.contains_key(&token.lexeme) {
+      return Err(Error::Runtime(
+        token.clone(),
+        format!("undefined variable '{}'", token.lexeme),
+      ));
+    }
+
+    globals.values.insert(token.lexeme.clone(), value);
+    Ok(())
+  }
+
+  // Index straight to the scope the resolver says `name` lives in, instead
+  // of walking the chain. Panics on a bad distance, since that means the
+  // resolver and interpreter have disagreed about the shape of the scope
+  // chain, which is a bug in us rather than in the Lox program.
+  pub fn get_at(&self, distance: usize, name: &str) -> LoxValue {
+    self
+      .ancestor(distance)
+      .borrow()
+      .values
+      .get(name)
+      .cloned()
+      .expect("resolver produced a bad distance")
+  }
+
+  pub fn assign_at(&mut self, distance: usize, name: &str, value: LoxValue) {
+    self
+      .ancestor(distance)
+      .borrow_mut()
+      .values
+      .insert(name.to_string(), value);
+  }
+
+  fn ancestor(&self, distance: usize) -> Rc<RefCell<Scope>> {
+    let mut scope = self.scope.clone();
+
+    for _ in 0..distance {
+      let parent = scope
+        .borrow()
+        .enclosing
+        .clone()
+        .expect("resolver produced a bad distance");
+
+      scope = parent;
+    }
+
+    scope
+  }
+}
@@ -0,0 +1,24 @@
+use crate::value::LoxValue;
+use crate::Token;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+  Parse(Token, String),
+  Resolve(Token, String),
+  Runtime(Token, String),
+  TryFrom(String),
+
+  // Raised by a native function, which doesn't have a Token on hand to
+  // blame.
+  Native(String),
+
+  // A static error with no single token to point at, e.g. a `break` that
+  // isn't inside any loop.
+  Compile(String),
+
+  // Not really an error: this is how a `return` statement unwinds back up
+  // through `execute` to whichever call site is waiting for it.
+  Return(LoxValue),
+}
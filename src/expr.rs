@@ -0,0 +1,29 @@
+use crate::Token;
+
+#[derive(Debug, Clone)]
+pub enum Literal {
+  Number(f64),
+  String(String),
+  Boolean(bool),
+  Nil,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+  Literal(Literal),
+  Grouping(Box<Expr>),
+  Unary(Token, Box<Expr>),
+  Binary(Box<Expr>, Token, Box<Expr>),
+  Variable(Token),
+  Assign(Token, Box<Expr>),
+  Logical(Box<Expr>, Token, Box<Expr>),
+  Call(Box<Expr>, Token, Vec<Box<Expr>>),
+}
+
+// Expr nodes don't carry their own identity, so the resolver's side table
+// (and the interpreter's lookup into it) key off of the address of the node
+// itself. This only works as long as a resolved tree is never deep-cloned
+// before it's executed.
+pub fn expr_id(expr: &Expr) -> usize {
+  expr as *const Expr as usize
+}
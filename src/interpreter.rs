@@ -1,19 +1,103 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use crate::environment::Environment;
-use crate::expr::Expr;
+use crate::expr::{expr_id, Expr};
 use crate::stmt::Stmt;
-use crate::value::LoxValue;
+use crate::value::{Func, LoxValue};
 use crate::{Error, Result, Token, TokenType as TT};
 
 #[derive(Debug)]
 pub struct Interpreter {
   env: Environment,
+  locals: HashMap<usize, usize>,
+}
+
+// What a statement tells its caller to do next. This mirrors the
+// halt-status propagation used by other tree-walkers to unwind `break`/
+// `continue` out of nested blocks without involving the error type.
+#[derive(Debug, PartialEq, Eq)]
+enum Flow {
+  Normal,
+  Break,
+  Continue,
 }
 
 impl Interpreter {
   pub fn new() -> Self {
-    Interpreter {
+    let mut interpreter = Interpreter {
       env: Environment::new(),
-    }
+      locals: HashMap::new(),
+    };
+
+    interpreter.register_stdlib();
+    interpreter
+  }
+
+  // Entry point for embedders to inject host functions into the
+  // interpreter's globals. `register_native` is a thin convenience layer
+  // over it for the common case of wrapping up a native `Func`.
+  pub fn define_global(&mut self, name: &str, value: LoxValue) {
+    self.env.define(name, value);
+  }
+
+  pub fn register_native(&mut self, name: &str, arity: usize, f: Box<Func>) {
+    self.define_global(name, LoxValue::new_callable(name.to_string(), arity, f));
+  }
+
+  fn register_stdlib(&mut self) {
+    self.register_native(
+      "clock",
+      0,
+      Box::new(|_, _| {
+        let now = SystemTime::now()
+          .duration_since(UNIX_EPOCH)
+          .expect("system clock is set before the unix epoch")
+          .as_secs_f64();
+
+        Ok(LoxValue::Number(now))
+      }),
+    );
+
+    self.register_native(
+      "str",
+      1,
+      Box::new(|_, mut args| Ok(LoxValue::String(args.remove(0).to_string()))),
+    );
+
+    // `Func` has no `Token` to blame, so a failure here raises `Error::Native`
+    // rather than the `Error::Runtime` a parse/type error would normally use
+    // elsewhere in the interpreter. This is a deliberate divergence, not an
+    // oversight.
+    self.register_native(
+      "num",
+      1,
+      Box::new(|_, mut args| match args.remove(0) {
+        LoxValue::String(s) => s
+          .trim()
+          .parse::<f64>()
+          .map(LoxValue::Number)
+          .map_err(|_| Error::Native(format!("'{}' is not a valid number", s))),
+        other => Err(Error::Native(format!(
+          "num() expects a string, got {}",
+          other
+        ))),
+      }),
+    );
+  }
+
+  // Takes the side table produced by `Resolver::resolve`, run over the same
+  // tree before it's handed to `interpret`. Variable/assignment lookups
+  // consult this first and only fall back to the global scope for names the
+  // resolver couldn't pin to a lexical distance.
+  //
+  // This merges into the existing table rather than replacing it, since the
+  // REPL calls `resolve` once per line but keeps the same `Interpreter`
+  // (and therefore the same closures) alive across lines - replacing the
+  // table outright would throw away the distances a function defined on an
+  // earlier line needs for the locals its closure captured.
+  pub fn resolve(&mut self, locals: HashMap<usize, usize>) {
+    self.locals.extend(locals);
   }
 
   pub fn interpret(&mut self, statements: Vec<Stmt>) -> Result<()> {
@@ -24,9 +108,16 @@ impl Interpreter {
     Ok(())
   }
 
-  fn execute(&mut self, stmt: &Stmt) -> Result<()> {
-    match stmt {
-      Stmt::Empty => (),
+  // Used by the REPL to evaluate and print a bare expression statement's
+  // value, instead of silently discarding it the way `Stmt::Expression`
+  // does in a script.
+  pub fn eval_top_level(&mut self, expr: &Box<Expr>) -> Result<LoxValue> {
+    self.eval_expr(expr)
+  }
+
+  fn execute(&mut self, stmt: &Stmt) -> Result<Flow> {
+    let flow = match stmt {
+      Stmt::Empty => Flow::Normal,
       Stmt::Block(block) => {
         self.env.push_scope();
 
@@ -34,30 +125,57 @@ impl Interpreter {
         // statement fails, but in reality we're going to propogate that all the
         // way up the stack and tear down anyway, so let's just not bother for
         // now.
+        let mut flow = Flow::Normal;
         for statement in block {
-          self.execute(statement)?;
+          flow = self.execute(statement)?;
+          if flow != Flow::Normal {
+            break;
+          }
         }
 
         self.env.pop_scope();
+        flow
       },
       Stmt::Expression(e) => {
         self.eval_expr(e)?;
+        Flow::Normal
       },
       Stmt::Print(e) => {
         let val = self.eval_expr(e)?;
         println!("{}", val);
+        Flow::Normal
       },
       Stmt::Var(name, init) => {
         let value = self.eval_expr(init)?;
         self.env.define(name, value);
+        Flow::Normal
+      },
+      Stmt::Function(name, params, body) => {
+        let callable = LoxValue::new_user_callable(
+          name.clone(),
+          params.clone(),
+          body.clone(),
+          self.env.clone(),
+        );
+
+        self.env.define(name, callable);
+        Flow::Normal
+      },
+      Stmt::Return(value) => {
+        let value = match value {
+          Some(e) => self.eval_expr(e)?,
+          None => LoxValue::Nil,
+        };
+
+        return Err(Error::Return(value));
       },
 
       // control flow
       Stmt::If(cond, then_branch, else_branch) => {
         if self.eval_expr(cond)?.is_truthy() {
-          self.execute(then_branch)?;
+          self.execute(then_branch)?
         } else {
-          self.execute(else_branch)?;
+          self.execute(else_branch)?
         }
       },
 
@@ -66,12 +184,41 @@ impl Interpreter {
         // the expr. I should reconsider that, maybe, but it wasn't trivially
         // doable, so let's get this working first.
         while self.eval_expr(cond)?.is_truthy() {
-          self.execute(body)?;
+          match self.execute(body)? {
+            Flow::Break => break,
+            Flow::Normal | Flow::Continue => (),
+          }
         }
+
+        Flow::Normal
       },
+
+      Stmt::Break => Flow::Break,
+      Stmt::Continue => Flow::Continue,
     };
 
-    Ok(())
+    Ok(flow)
+  }
+
+  // Runs `stmts` with `env` swapped in as the current environment, then
+  // restores whatever was there before, even if a statement bails out with
+  // an error (including the control-flow `Error::Return`). This is how a
+  // function call gets its own scope enclosed by the closure it captured.
+  pub(crate) fn execute_block(&mut self, stmts: &[Stmt], env: Environment) -> Result<()> {
+    let previous = std::mem::replace(&mut self.env, env);
+
+    let result = (|| {
+      for stmt in stmts {
+        // A bare `break`/`continue` can't reach here without an enclosing
+        // loop, which the resolver already rejects; a function body just
+        // runs straight through.
+        self.execute(stmt)?;
+      }
+      Ok(())
+    })();
+
+    self.env = previous;
+    result
   }
 
   fn eval_expr(&mut self, expr: &Box<Expr>) -> Result<LoxValue> {
@@ -82,12 +229,47 @@ impl Interpreter {
       Expr::Binary(ref left, ref op, ref right) => {
         self.eval_binary_expr(left, op, right)?
       },
-      Expr::Variable(ref token) => self.env.get(token)?,
-      Expr::Assign(token, expr) => {
-        let value = self.eval_expr(&expr)?;
-        self.env.assign(&token, value.clone())?;
+      Expr::Variable(ref token) => self.look_up_variable(token, expr.as_ref())?,
+      Expr::Assign(token, value_expr) => {
+        let value = self.eval_expr(value_expr)?;
+
+        match self.locals.get(&expr_id(expr)) {
+          Some(&distance) => self.env.assign_at(distance, &token.lexeme, value.clone()),
+          None => self.env.assign_global(token, value.clone())?,
+        }
+
         value
       },
+      Expr::Call(callee, paren, args) => {
+        let callee = self.eval_expr(callee)?;
+
+        if !callee.is_callable() {
+          return Err(Error::Runtime(
+            paren.clone(),
+            "can only call functions and classes".into(),
+          ));
+        }
+
+        let mut arg_values = Vec::with_capacity(args.len());
+        for arg in args {
+          arg_values.push(self.eval_expr(arg)?);
+        }
+
+        let callable = callee.as_callable();
+
+        if arg_values.len() != callable.arity() {
+          return Err(Error::Runtime(
+            paren.clone(),
+            format!(
+              "expected {} arguments but got {}",
+              callable.arity(),
+              arg_values.len()
+            ),
+          ));
+        }
+
+        callable.call(self, arg_values)?
+      },
       Expr::Logical(left, op, right) => {
         let left_val = self.eval_expr(&left)?;
         let left_true = left_val.is_truthy();
@@ -111,6 +293,13 @@ impl Interpreter {
     Ok(val)
   }
 
+  fn look_up_variable(&self, token: &Token, expr: &Expr) -> Result<LoxValue> {
+    match self.locals.get(&expr_id(expr)) {
+      Some(&distance) => Ok(self.env.get_at(distance, &token.lexeme)),
+      None => self.env.get_global(token),
+    }
+  }
+
   fn eval_unary_expr(&mut self, op: &Token, right: &Box<Expr>) -> Result<LoxValue> {
     let right = self.eval_expr(right)?;
 
@@ -172,22 +361,9 @@ impl Interpreter {
         },
       },
 
-      // numbers, though I think maybe they should work on strings too.
-      TT::Greater => {
-        assert_two_numbers(op, &left, &right)?;
-        LV::Boolean(left.as_number() > right.as_number())
-      },
-      TT::GreaterEqual => {
-        assert_two_numbers(op, &left, &right)?;
-        LV::Boolean(left.as_number() >= right.as_number())
-      },
-      TT::Less => {
-        assert_two_numbers(op, &left, &right)?;
-        LV::Boolean(left.as_number() < right.as_number())
-      },
-      TT::LessEqual => {
-        assert_two_numbers(op, &left, &right)?;
-        LV::Boolean(left.as_number() <= right.as_number())
+      // relational operators work on two numbers or two strings
+      TT::Greater | TT::GreaterEqual | TT::Less | TT::LessEqual => {
+        LV::Boolean(compare(op, &left, &right)?)
       },
       _ => unreachable!(),
     };
@@ -206,3 +382,30 @@ fn assert_two_numbers(op: &Token, left: &LoxValue, right: &LoxValue) -> Result<(
     ))
   }
 }
+
+fn compare(op: &Token, left: &LoxValue, right: &LoxValue) -> Result<bool> {
+  use LoxValue as LV;
+
+  // Dispatch on type first and compare with the native `<`/`>` operators
+  // directly, rather than going through `Ord`, so that NaN comparisons keep
+  // their usual IEEE-754 behavior (always false) instead of becoming a
+  // runtime error.
+  match (left, right) {
+    (LV::Number(a), LV::Number(b)) => Ok(apply(op, a, b)),
+    (LV::String(a), LV::String(b)) => Ok(apply(op, a, b)),
+    _ => Err(Error::Runtime(
+      op.clone(),
+      format!("'{}' needs two numbers or two strings", op.lexeme),
+    )),
+  }
+}
+
+fn apply<T: PartialOrd>(op: &Token, left: T, right: T) -> bool {
+  match op.kind {
+    TT::Greater => left > right,
+    TT::GreaterEqual => left >= right,
+    TT::Less => left < right,
+    TT::LessEqual => left <= right,
+    _ => unreachable!("compare called with a non-comparison operator"),
+  }
+}
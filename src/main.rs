@@ -0,0 +1,131 @@
+use std::io::{self, Write};
+use std::process;
+use std::{env, fs};
+
+use lox::resolver::Resolver;
+use lox::tools::ast_printer;
+use lox::{Error, Interpreter, Parser, Scanner, Stmt};
+
+fn main() {
+  let args: Vec<String> = env::args().collect();
+
+  match args.as_slice() {
+    [_] => run_prompt(),
+    [_, mode, path] => run_file(mode, path),
+    _ => {
+      eprintln!("usage: lox [tokenize|parse|evaluate] <script>");
+      process::exit(64);
+    },
+  }
+}
+
+fn run_file(mode: &str, path: &str) {
+  let source = fs::read_to_string(path).unwrap_or_else(|e| {
+    eprintln!("couldn't read {}: {}", path, e);
+    process::exit(66);
+  });
+
+  match mode {
+    "tokenize" => {
+      for token in Scanner::new(&source).scan_tokens() {
+        println!("{:?}", token);
+      }
+    },
+    "parse" => match parse(&source) {
+      Ok(stmts) => {
+        for stmt in &stmts {
+          ast_printer::print_stmt(stmt);
+        }
+      },
+      Err(e) => exit_with_error(&e, 65),
+    },
+    "evaluate" => {
+      let stmts = parse(&source).unwrap_or_else(|e| exit_with_error(&e, 65));
+      let mut interpreter = Interpreter::new();
+
+      let locals = Resolver::new()
+        .resolve(&stmts)
+        .unwrap_or_else(|e| exit_with_error(&e, 65));
+
+      interpreter.resolve(locals);
+
+      if let Err(e) = interpreter.interpret(stmts) {
+        exit_with_error(&e, 70);
+      }
+    },
+    _ => {
+      eprintln!("unknown mode '{}' (expected tokenize, parse, or evaluate)", mode);
+      process::exit(64);
+    },
+  }
+}
+
+// Keeps one Interpreter alive across every line, so variables and function
+// definitions from earlier input stay visible, and prints the value of a
+// bare expression statement instead of silently discarding it.
+fn run_prompt() {
+  let mut interpreter = Interpreter::new();
+  let stdin = io::stdin();
+
+  loop {
+    print!("> ");
+    io::stdout().flush().ok();
+
+    let mut line = String::new();
+    if stdin.read_line(&mut line).unwrap_or(0) == 0 {
+      println!();
+      break;
+    }
+
+    let line = line.trim_end();
+    if line.is_empty() {
+      continue;
+    }
+
+    run_line(&mut interpreter, line);
+  }
+}
+
+fn run_line(interpreter: &mut Interpreter, line: &str) {
+  let stmts = match parse(line) {
+    Ok(stmts) => stmts,
+    Err(e) => return report_error(&e),
+  };
+
+  // Resolve this line's tree before touching the interpreter, every time -
+  // the `locals` table is keyed by node address, and leaving a previous
+  // line's table in place would let a reused address false-hit a distance
+  // that belongs to an entirely different variable.
+  let locals = match Resolver::new().resolve(&stmts) {
+    Ok(locals) => locals,
+    Err(e) => return report_error(&e),
+  };
+
+  interpreter.resolve(locals);
+
+  if let [Stmt::Expression(expr)] = stmts.as_slice() {
+    match interpreter.eval_top_level(expr) {
+      Ok(value) => println!("{}", value),
+      Err(e) => report_error(&e),
+    }
+    return;
+  }
+
+  if let Err(e) = interpreter.interpret(stmts) {
+    report_error(&e);
+  }
+}
+
+fn parse(source: &str) -> lox::Result<Vec<Stmt>> {
+  let tokens = Scanner::new(source).scan_tokens();
+  Parser::new(tokens).parse()
+}
+
+fn exit_with_error(err: &Error, code: i32) -> ! {
+  report_error(err);
+  process::exit(code);
+}
+
+fn report_error(err: &Error) {
+  eprintln!("{:?}", err);
+}
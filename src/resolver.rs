@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use crate::expr::{expr_id, Expr};
+use crate::stmt::Stmt;
+use crate::{Error, Result, Token};
+
+// Walks the parsed tree once, before interpretation, to figure out exactly
+// which enclosing scope each variable reference resolves to. This fixes a
+// closure created inside a loop (or any re-entered block) from capturing
+// the wrong binding when the interpreter's dynamic `Environment` lookup
+// would otherwise have to guess at runtime.
+#[derive(Debug, Default)]
+pub struct Resolver {
+  scopes: Vec<HashMap<String, bool>>,
+  locals: HashMap<usize, usize>,
+  loop_depth: usize,
+  function_depth: usize,
+}
+
+impl Resolver {
+  pub fn new() -> Self {
+    Resolver::default()
+  }
+
+  // Consumes the resolver and hands back the side table of resolved
+  // distances, keyed by `expr_id`, for the interpreter to consult.
+  pub fn resolve(mut self, statements: &[Stmt]) -> Result<HashMap<usize, usize>> {
+    self.resolve_stmts(statements)?;
+    Ok(self.locals)
+  }
+
+  fn resolve_stmts(&mut self, statements: &[Stmt]) -> Result<()> {
+    for stmt in statements {
+      self.resolve_stmt(stmt)?;
+    }
+
+    Ok(())
+  }
+
+  fn resolve_stmt(&mut self, stmt: &Stmt) -> Result<()> {
+    match stmt {
+      Stmt::Empty => (),
+      Stmt::Block(stmts) => {
+        self.begin_scope();
+        self.resolve_stmts(stmts)?;
+        self.end_scope();
+      },
+      Stmt::Expression(e) => self.resolve_expr(e)?,
+      Stmt::Print(e) => self.resolve_expr(e)?,
+      Stmt::Var(name, init) => {
+        self.declare(name);
+        self.resolve_expr(init)?;
+        self.define(name);
+      },
+      Stmt::Function(name, params, body) => {
+        // Declare and define the name before resolving the body, so the
+        // function can call itself recursively.
+        self.declare(name);
+        self.define(name);
+        self.resolve_function(params, body)?;
+      },
+      Stmt::Return(value) => {
+        if self.function_depth == 0 {
+          return Err(Error::Compile("can't return from outside of a function".into()));
+        }
+
+        if let Some(e) = value {
+          self.resolve_expr(e)?;
+        }
+      },
+      Stmt::If(cond, then_branch, else_branch) => {
+        self.resolve_expr(cond)?;
+        self.resolve_stmt(then_branch)?;
+        self.resolve_stmt(else_branch)?;
+      },
+      Stmt::While(cond, body) => {
+        self.resolve_expr(cond)?;
+        self.loop_depth += 1;
+        let result = self.resolve_stmt(body);
+        self.loop_depth -= 1;
+        result?;
+      },
+      Stmt::Break => {
+        if self.loop_depth == 0 {
+          return Err(Error::Compile("can't use 'break' outside of a loop".into()));
+        }
+      },
+      Stmt::Continue => {
+        if self.loop_depth == 0 {
+          return Err(Error::Compile(
+            "can't use 'continue' outside of a loop".into(),
+          ));
+        }
+      },
+    }
+
+    Ok(())
+  }
+
+  fn resolve_function(&mut self, params: &[String], body: &[Stmt]) -> Result<()> {
+    self.begin_scope();
+
+    for param in params {
+      self.declare(param);
+      self.define(param);
+    }
+
+    // A loop enclosing a `fun` declaration shouldn't let a `break`/`continue`
+    // inside the function body reach back out to it.
+    let enclosing_loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+    self.function_depth += 1;
+    let result = self.resolve_stmts(body);
+    self.function_depth -= 1;
+    self.loop_depth = enclosing_loop_depth;
+    result?;
+
+    self.end_scope();
+    Ok(())
+  }
+
+  fn resolve_expr(&mut self, expr: &Box<Expr>) -> Result<()> {
+    match expr.as_ref() {
+      Expr::Literal(_) => (),
+      Expr::Grouping(e) => self.resolve_expr(e)?,
+      Expr::Unary(_, right) => self.resolve_expr(right)?,
+      Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+        self.resolve_expr(left)?;
+        self.resolve_expr(right)?;
+      },
+      Expr::Call(callee, _paren, args) => {
+        self.resolve_expr(callee)?;
+        for arg in args {
+          self.resolve_expr(arg)?;
+        }
+      },
+      Expr::Variable(token) => {
+        if let Some(scope) = self.scopes.last() {
+          if scope.get(&token.lexeme) == Some(&false) {
+            return Err(Error::Resolve(
+              token.clone(),
+              "can't read local variable in its own initializer".into(),
+            ));
+          }
+        }
+
+        self.resolve_local(expr, token);
+      },
+      Expr::Assign(token, value) => {
+        self.resolve_expr(value)?;
+        self.resolve_local(expr, token);
+      },
+    }
+
+    Ok(())
+  }
+
+  fn resolve_local(&mut self, expr: &Expr, token: &Token) {
+    for (distance, scope) in self.scopes.iter().rev().enumerate() {
+      if scope.contains_key(&token.lexeme) {
+        self.locals.insert(expr_id(expr), distance);
+        return;
+      }
+    }
+
+    // Not found in any local scope; the interpreter treats this as a
+    // global, which it'll look up dynamically at runtime.
+  }
+
+  fn begin_scope(&mut self) {
+    self.scopes.push(HashMap::new());
+  }
+
+  fn end_scope(&mut self) {
+    self.scopes.pop();
+  }
+
+  fn declare(&mut self, name: &str) {
+    if let Some(scope) = self.scopes.last_mut() {
+      scope.insert(name.to_string(), false);
+    }
+  }
+
+  fn define(&mut self, name: &str) {
+    if let Some(scope) = self.scopes.last_mut() {
+      scope.insert(name.to_string(), true);
+    }
+  }
+}
@@ -1,11 +1,18 @@
+use std::rc::Rc;
+
 use crate::expr::Expr;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Stmt {
   Empty,
   Block(Vec<Stmt>),
+  Break,
+  Continue,
   Expression(Box<Expr>),
+  Function(String, Vec<String>, Rc<Vec<Stmt>>),
   If(Box<Expr>, Box<Stmt>, Box<Stmt>),
   Print(Box<Expr>),
+  Return(Option<Box<Expr>>),
   Var(String, Box<Expr>), // maybe instead, Option<Expr>
+  While(Box<Expr>, Box<Stmt>),
 }
@@ -0,0 +1,77 @@
+use crate::expr::{Expr, Literal};
+use crate::stmt::Stmt;
+
+pub fn print_ast(expr: Box<Expr>) {
+  println!("{}", format_expr(&expr));
+}
+
+pub fn print_stmt(stmt: &Stmt) {
+  println!("{}", format_stmt(stmt));
+}
+
+fn format_stmt(stmt: &Stmt) -> String {
+  match stmt {
+    Stmt::Empty => String::new(),
+    Stmt::Block(stmts) => {
+      let body: Vec<String> = stmts.iter().map(format_stmt).collect();
+      format!("(block {})", body.join(" "))
+    },
+    Stmt::Break => "(break)".into(),
+    Stmt::Continue => "(continue)".into(),
+    Stmt::Expression(e) => format_expr(e),
+    Stmt::Function(name, params, body) => {
+      let body: Vec<String> = body.iter().map(format_stmt).collect();
+      format!("(fun {} ({}) {})", name, params.join(" "), body.join(" "))
+    },
+    Stmt::If(cond, then_branch, else_branch) => format!(
+      "(if {} {} {})",
+      format_expr(cond),
+      format_stmt(then_branch),
+      format_stmt(else_branch)
+    ),
+    Stmt::Print(e) => format!("(print {})", format_expr(e)),
+    Stmt::Return(value) => match value {
+      Some(e) => format!("(return {})", format_expr(e)),
+      None => "(return)".into(),
+    },
+    Stmt::Var(name, init) => format!("(var {} {})", name, format_expr(init)),
+    Stmt::While(cond, body) => format!("(while {} {})", format_expr(cond), format_stmt(body)),
+  }
+}
+
+fn format_expr(expr: &Expr) -> String {
+  match expr {
+    Expr::Literal(lit) => format_literal(lit),
+    Expr::Grouping(e) => parenthesize("group", &[e]),
+    Expr::Unary(op, right) => parenthesize(&op.lexeme, &[right]),
+    Expr::Binary(left, op, right) => parenthesize(&op.lexeme, &[left, right]),
+    Expr::Logical(left, op, right) => parenthesize(&op.lexeme, &[left, right]),
+    Expr::Variable(token) => token.lexeme.clone(),
+    Expr::Assign(token, value) => format!("(= {} {})", token.lexeme, format_expr(value)),
+    Expr::Call(callee, _paren, args) => {
+      let args: Vec<String> = args.iter().map(|a| format_expr(a)).collect();
+      format!("(call {} {})", format_expr(callee), args.join(" "))
+    },
+  }
+}
+
+fn format_literal(lit: &Literal) -> String {
+  match lit {
+    Literal::Number(n) => n.to_string(),
+    Literal::String(s) => s.clone(),
+    Literal::Boolean(b) => b.to_string(),
+    Literal::Nil => "nil".into(),
+  }
+}
+
+fn parenthesize(name: &str, exprs: &[&Box<Expr>]) -> String {
+  let mut out = format!("({}", name);
+
+  for e in exprs {
+    out.push(' ');
+    out.push_str(&format_expr(e));
+  }
+
+  out.push(')');
+  out
+}
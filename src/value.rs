@@ -1,6 +1,10 @@
 mod callable;
 
+use std::rc::Rc;
+
+use crate::environment::Environment;
 use crate::expr::Literal;
+use crate::stmt::Stmt;
 use crate::{Error, Interpreter, Result};
 pub use callable::Callable;
 
@@ -22,6 +26,15 @@ impl LoxValue {
     LoxValue::Function(Box::new(Callable::new(name, arity, func)))
   }
 
+  pub fn new_user_callable(
+    name: String,
+    params: Vec<String>,
+    body: Rc<Vec<Stmt>>,
+    closure: Environment,
+  ) -> Self {
+    LoxValue::Function(Box::new(Callable::new_user(name, params, body, closure)))
+  }
+
   pub fn is_truthy(&self) -> bool {
     match self {
       Self::Nil => false,
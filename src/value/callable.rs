@@ -0,0 +1,84 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::environment::Environment;
+use crate::stmt::Stmt;
+use crate::value::{Func, LoxValue};
+use crate::{Error, Interpreter, Result};
+
+#[derive(Clone)]
+pub struct Callable {
+  pub name: String,
+  arity: usize,
+  kind: Kind,
+}
+
+#[derive(Clone)]
+enum Kind {
+  Native(Rc<Func>),
+  User {
+    params: Rc<Vec<String>>,
+    body: Rc<Vec<Stmt>>,
+    closure: Environment,
+  },
+}
+
+impl Callable {
+  pub fn new(name: String, arity: usize, func: Box<Func>) -> Self {
+    Callable {
+      name,
+      arity,
+      kind: Kind::Native(Rc::from(func)),
+    }
+  }
+
+  pub fn new_user(
+    name: String,
+    params: Vec<String>,
+    body: Rc<Vec<Stmt>>,
+    closure: Environment,
+  ) -> Self {
+    Callable {
+      name,
+      arity: params.len(),
+      kind: Kind::User {
+        params: Rc::new(params),
+        body,
+        closure,
+      },
+    }
+  }
+
+  pub fn arity(&self) -> usize {
+    self.arity
+  }
+
+  pub fn call(&self, interpreter: &mut Interpreter, args: Vec<LoxValue>) -> Result<LoxValue> {
+    match &self.kind {
+      Kind::Native(func) => func(interpreter, args),
+      Kind::User {
+        params,
+        body,
+        closure,
+      } => {
+        let mut call_env = closure.child();
+
+        for (param, arg) in params.iter().zip(args) {
+          call_env.define(param, arg);
+        }
+
+        match interpreter.execute_block(body, call_env) {
+          Ok(()) => Ok(LoxValue::Nil),
+          Err(Error::Return(value)) => Ok(value),
+          Err(e) => Err(e),
+        }
+      },
+    }
+  }
+}
+
+impl fmt::Debug for Callable {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "<fn {}>", self.name)
+  }
+}